@@ -0,0 +1,174 @@
+// Symphonia
+// Copyright (c) 2021 The Project Symphonia Developers.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at https://mozilla.org/MPL/2.0/.
+
+//! The `fft` module implements a radix-2 complex Fast Fourier Transform (FFT).
+//!
+//! This is a general purpose, but not particularly sophisticated, FFT. It exists primarily to
+//! back the FFT-based IMDCT backend in the `mdct` module.
+
+use std::f64;
+use std::ops::{Add, Mul, Sub};
+
+/// A single-precision complex number.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Complex32 {
+    pub re: f32,
+    pub im: f32,
+}
+
+impl Complex32 {
+    pub fn new(re: f32, im: f32) -> Complex32 {
+        Complex32 { re, im }
+    }
+
+    fn conj(self) -> Complex32 {
+        Complex32::new(self.re, -self.im)
+    }
+}
+
+impl Add for Complex32 {
+    type Output = Complex32;
+
+    fn add(self, rhs: Complex32) -> Complex32 {
+        Complex32::new(self.re + rhs.re, self.im + rhs.im)
+    }
+}
+
+impl Sub for Complex32 {
+    type Output = Complex32;
+
+    fn sub(self, rhs: Complex32) -> Complex32 {
+        Complex32::new(self.re - rhs.re, self.im - rhs.im)
+    }
+}
+
+impl Mul for Complex32 {
+    type Output = Complex32;
+
+    fn mul(self, rhs: Complex32) -> Complex32 {
+        Complex32::new(self.re * rhs.re - self.im * rhs.im, self.re * rhs.im + self.im * rhs.re)
+    }
+}
+
+/// A radix-2 complex FFT of a fixed, power-of-two, length.
+pub struct Fft {
+    n: u32,
+    /// Forward twiddle factors, `exp(-2*pi*i*k/n)`, for `k` in `0..n/2`.
+    twiddles: Vec<Complex32>,
+}
+
+impl Fft {
+    /// Instantiate a N-point FFT.
+    ///
+    /// The value of `n` must be a power-of-2.
+    pub fn new(n: u32) -> Fft {
+        assert!(n.is_power_of_two(), "n must be a power of two");
+
+        let c = -2.0 * f64::consts::PI / f64::from(n);
+
+        let twiddles = (0..n / 2)
+            .map(|k| {
+                let theta = c * f64::from(k);
+                Complex32::new(theta.cos() as f32, theta.sin() as f32)
+            })
+            .collect();
+
+        Fft { n, twiddles }
+    }
+
+    /// Performs an in-place N-point forward FFT.
+    ///
+    /// The length of `buf` must equal the value `Fft` was instantiated with.
+    pub fn fft(&self, buf: &mut [Complex32]) {
+        self.transform(buf, false);
+    }
+
+    /// Performs an in-place N-point inverse FFT.
+    ///
+    /// The length of `buf` must equal the value `Fft` was instantiated with. The output is
+    /// normalized by `1/N`.
+    pub fn ifft(&self, buf: &mut [Complex32]) {
+        self.transform(buf, true);
+    }
+
+    fn transform(&self, buf: &mut [Complex32], inverse: bool) {
+        let n = self.n as usize;
+
+        assert_eq!(buf.len(), n);
+
+        // Bit-reversal permutation.
+        let bits = n.trailing_zeros();
+
+        for i in 0..n {
+            let j = i.reverse_bits() >> (usize::BITS - bits);
+
+            if j > i {
+                buf.swap(i, j);
+            }
+        }
+
+        // Iterative Cooley-Tukey radix-2 decimation-in-time butterflies.
+        let mut size = 2;
+
+        while size <= n {
+            let half = size / 2;
+            let stride = n / size;
+
+            for start in (0..n).step_by(size) {
+                for k in 0..half {
+                    let mut tw = self.twiddles[k * stride];
+
+                    if inverse {
+                        tw = tw.conj();
+                    }
+
+                    let u = buf[start + k];
+                    let v = buf[start + k + half] * tw;
+
+                    buf[start + k] = u + v;
+                    buf[start + k + half] = u - v;
+                }
+            }
+
+            size <<= 1;
+        }
+
+        if inverse {
+            let scale = 1.0 / n as f32;
+
+            for s in buf.iter_mut() {
+                s.re *= scale;
+                s.im *= scale;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verify_fft_roundtrip() {
+        const TEST_VECTOR: [f32; 16] = [
+            1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0, 11.0, 12.0, 13.0, 14.0, 15.0, 16.0,
+        ];
+
+        let fft = Fft::new(16);
+
+        let mut buf: Vec<Complex32> =
+            TEST_VECTOR.iter().map(|&re| Complex32::new(re, 0.0)).collect();
+
+        fft.fft(&mut buf);
+        fft.ifft(&mut buf);
+
+        for (actual, &expected) in buf.iter().zip(&TEST_VECTOR) {
+            assert!((actual.re - expected).abs() < 0.0001);
+            assert!(actual.im.abs() < 0.0001);
+        }
+    }
+}
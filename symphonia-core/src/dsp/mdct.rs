@@ -13,10 +13,48 @@
 use std::f64;
 
 use super::dct::Dct;
+use super::fft::{Complex32, Fft};
+
+/// Selects the algorithm `Imdct` uses internally to compute the transform.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImdctBackend {
+    /// Route the IMDCT through a DCT-II of length N computed directly from its definition
+    /// (`Dct`'s `dct_ii_inplace`). This is `O(N^2)`, but places no constraint on N beyond the
+    /// divisible-by-4 requirement `Imdct` itself imposes, so it is the only backend that can
+    /// serve odd-feeling sizes such as MP3's N=12/36 blocks. This is the default.
+    Dct,
+    /// Route the IMDCT through a DCT-II of length N computed in terms of a complex FFT of length
+    /// N/2, rather than `Dct`'s direct DCT-II. This is the fast path for power-of-two N: it is
+    /// `O(N log N)` and faster than the `Dct` backend for large N (e.g. the 8192-point transforms
+    /// this module allows), but, because it bottoms out in a power-of-two complex FFT, it
+    /// additionally requires N/2 to be a power-of-two.
+    Fft,
+}
+
+enum Backend {
+    Dct { dct: Dct, table: Vec<f32> },
+    Fft {
+        fft: Fft,
+        table: Vec<f32>,
+        // Precomputed real-FFT unpacking twiddles, `-i * exp(-i*2*pi*k/n)`, for k=0..=n/2. Used
+        // by `dct_ii_via_fft` to avoid recomputing a `cos`/`sin` pair per element on every call.
+        unpack_tw: Vec<Complex32>,
+        // Precomputed final DCT-II rotation twiddles, `exp(-i*pi*k/(2n))`, for k=0..n.
+        rot_tw: Vec<Complex32>,
+        // Scratch buffers for `dct_ii_via_fft`, sized once here so the hot path performs no
+        // per-call heap allocation.
+        v: Vec<f32>,
+        c: Vec<Complex32>,
+        vv: Vec<Complex32>,
+    },
+}
 
 /// Inverse Modified Discrete Transform (IMDCT).
 ///
-/// Implements the IMDCT in-terms of a DCT-IV as described in \[1\] and \[2\].
+/// Implements the IMDCT in-terms of a DCT-IV as described in \[1\] and \[2\]. Alternatively, via
+/// [`ImdctBackend::Fft`], the same DCT-II-to-DCT-IV-to-IMDCT pipeline is used, but the DCT-II
+/// itself is computed from a complex FFT of length N/2 using the real-FFT-via-half-length-
+/// complex-FFT technique described in \[3\], rather than directly.
 ///
 /// \[1\] Mu-Huo Cheng and Yu-Hsin Hsu, "Fast IMDCT and MDCT algorithms - a matrix approach,"
 ///       in IEEE Transactions on Signal Processing, vol. 51, no. 1, pp. 221-229, Jan. 2003,
@@ -26,33 +64,92 @@ use super::dct::Dct;
 ///       MDCT/IMDCT in modern audio coding standards," 2007 International Symposium on
 ///       Communications and Information Technologies, Sydney, NSW, 2007, pp. 546-550,
 ///       doi: 10.1109/ISCIT.2007.4392079.
+///
+/// \[3\] J. Makhoul, "A fast cosine transform in one and two dimensions," in IEEE Transactions
+///       on Acoustics, Speech, and Signal Processing, vol. 28, no. 1, pp. 27-34, Feb. 1980,
+///       doi: 10.1109/TASSP.1980.1163351.
 pub struct Imdct {
-    dct: Dct,
+    backend: Backend,
     n: u32,
-    table: Vec<f32>,
 }
 
 impl Imdct {
-    /// Instantiate a N-point IMDCT.
+    /// Instantiate a N-point IMDCT using the default ([`ImdctBackend::Dct`]) backend.
     ///
-    /// The value of `n` must be a power-of-2, and less-than or equal to 8192.
+    /// The value of `n` must be divisible by 4, and less-than or equal to 8192. This permits,
+    /// amongst other sizes, the N=12 and N=36 blocks used by MP3, in addition to the
+    /// power-of-two sizes used by most other codecs.
     pub fn new(n: u32) -> Imdct {
-        // The algorithm implemented requires a power-of-two N.
-        assert!(n.is_power_of_two(), "n must be a power of two");
+        Imdct::with_backend(n, ImdctBackend::Dct)
+    }
+
+    /// Instantiate a N-point IMDCT using the specified backend.
+    ///
+    /// The value of `n` must be divisible by 4, and less-than or equal to 8192. The
+    /// [`ImdctBackend::Fft`] backend additionally requires N/2 to be a power-of-2, as it is
+    /// implemented in terms of a power-of-two complex FFT.
+    pub fn with_backend(n: u32, backend: ImdctBackend) -> Imdct {
+        // This module reduces the IMDCT to a DCT-II of length N, followed by a region-remapping
+        // over four N/2-sample regions of the 2N-sample output. N is required to be divisible
+        // by 4, which covers the power-of-two sizes used by most codecs.
+        //
+        // This constraint is safe for the default `Dct` backend regardless of N's
+        // power-of-two-ness: `Dct::dct_ii_inplace` evaluates the DCT-II directly from its
+        // definition (see `Mdct`'s doc, which relies on this same fact), not via a power-of-two
+        // FFT, so it has no additional size restriction of its own. `ImdctBackend::Fft` is the
+        // distinct power-of-two fast path and enforces its own, stricter requirement below.
+        assert_eq!(n % 4, 0, "n must be divisible by 4");
         // This limitation is somewhat arbitrary, but a limit must be set somewhere.
         assert!(n <= 8192, "maximum of 8192-point imdct");
 
+        // Both backends pre-rotate the input by the same cosine table and share the DCT-II to
+        // IMDCT region-mapping code; they only differ in how the intermediate DCT-II is
+        // computed.
         let c = f64::consts::PI / f64::from(2 * 2 * n);
 
-        let table: Vec<f32> = (0..n)
-            .map(|i| (2.0 * (c * f64::from(2 * i + 1)).cos()) as f32)
-            .collect();
+        let table: Vec<f32> =
+            (0..n).map(|i| (2.0 * (c * f64::from(2 * i + 1)).cos()) as f32).collect();
 
-        Imdct {
-            dct: Dct::new(n),
-            n,
-            table,
-        }
+        let backend = match backend {
+            ImdctBackend::Dct => Backend::Dct { dct: Dct::new(n), table },
+            ImdctBackend::Fft => {
+                let nh = n / 2;
+
+                assert!(nh.is_power_of_two(), "fft backend requires n/2 to be a power of two");
+
+                let n_usize = n as usize;
+                let nh_usize = nh as usize;
+
+                // Both twiddle tables depend only on `n`, not on the transform's input, so they
+                // are computed once here rather than on every `imdct` call.
+                let unpack_tw: Vec<Complex32> = (0..=nh_usize)
+                    .map(|k| {
+                        let theta = -2.0 * f64::consts::PI * k as f64 / n_usize as f64;
+                        let tw = Complex32::new(theta.cos() as f32, theta.sin() as f32);
+                        Complex32::new(tw.im, -tw.re)
+                    })
+                    .collect();
+
+                let rot_tw: Vec<Complex32> = (0..n_usize)
+                    .map(|k| {
+                        let theta = -f64::consts::PI * k as f64 / (2.0 * n_usize as f64);
+                        Complex32::new(theta.cos() as f32, theta.sin() as f32)
+                    })
+                    .collect();
+
+                Backend::Fft {
+                    fft: Fft::new(nh),
+                    table,
+                    unpack_tw,
+                    rot_tw,
+                    v: vec![0f32; n_usize],
+                    c: vec![Complex32::default(); nh_usize],
+                    vv: vec![Complex32::default(); nh_usize + 1],
+                }
+            }
+        };
+
+        Imdct { backend, n }
     }
 
     /// Performs the the N-point Inverse Modified Discrete Cosine Transform.
@@ -65,68 +162,637 @@ impl Imdct {
     /// scale will equal `sqrt(1.0 / N)` where N is the number of input samples, though each
     /// application will vary.
     pub fn imdct(&mut self, src: &[f32], dst: &mut [f32], scale: f32) {
-        // The IMDCT produces 2N samples for N inputs. This algorithm defines the ouput length as
-        // N.
+        let n2 = self.n as usize;
+
+        assert_eq!(dst.len(), n2 << 1);
+        assert_eq!(src.len(), n2);
+
+        self.prerotate(src, dst);
+        self.dct_ii_and_remap(dst, scale);
+    }
+
+    /// Pre-rotates `src` by this `Imdct`'s cosine table, writing the result into the second half
+    /// of `dst` (a buffer of length 2N). This is `imdct`'s first stage, split out so
+    /// `ImdctImdst` can compute it once and derive both the IMDCT's and the paired IMDST's
+    /// DCT-II input from it, rather than repeating this multiply for each.
+    fn prerotate(&self, src: &[f32], dst: &mut [f32]) {
+        let n2 = self.n as usize;
+
+        let table = match &self.backend {
+            Backend::Dct { table, .. } => table,
+            Backend::Fft { table, .. } => table,
+        };
+
+        for ((ds, &src), &cos) in dst[n2..].iter_mut().zip(src).zip(table) {
+            *ds = src * cos;
+        }
+    }
+
+    /// Computes the DCT-II in-place over the second half of `dst` (as left there by
+    /// [`Imdct::prerotate`]) and expands it into the full IMDCT via [`dct_ii_to_imdct`]. This is
+    /// `imdct`'s second stage, unlike the first unable to be shared with a paired IMDST since it
+    /// is specific to each transform's (pre-rotated and possibly sign-flipped) input.
+    fn dct_ii_and_remap(&mut self, dst: &mut [f32], scale: f32) {
+        let n2 = self.n as usize;
+
+        match &mut self.backend {
+            Backend::Dct { dct, .. } => dct.dct_ii_inplace(&mut dst[n2..]),
+            Backend::Fft { fft, unpack_tw, rot_tw, v, c, vv, .. } => {
+                dct_ii_via_fft(fft, unpack_tw, rot_tw, v, c, vv, &mut dst[n2..])
+            }
+        }
+
+        dct_ii_to_imdct(dst, scale);
+    }
+}
+
+/// Converts an in-place DCT-II of length N, stored in the second-half of `dst` (a buffer of
+/// length 2N), into the final N-point IMDCT, via the DCT-II to DCT-IV region-remapping
+/// recurrence, written into the whole of `dst`.
+fn dct_ii_to_imdct(dst: &mut [f32], scale: f32) {
+    let n = dst.len();
+    let n4 = n >> 2;
+
+    // DCT-II to DCT-IV
+    //
+    // Split dst into 4 evenly sized N/2 regions: [ a, b, c, d ]. Regions c & d contain the
+    // DCT-II transformed samples from the previous step. After this step, regions b & c will
+    // contain the DCT-II transformed samples.
+    let (a, b) = dst.split_at_mut(n4);
+    let (b, c) = b.split_at_mut(n4);
+    let (c, d) = c.split_at_mut(n4);
+
+    // Map c to b.
+    b[0] = -0.5 * c[0];
+
+    for i in 1..n4 {
+        b[i] = -1.0 * (c[i] + b[i - 1]);
+    }
+
+    // Map d to c.
+    c[0] = d[0] + b[n4 - 1];
+
+    for i in 1..n4 {
+        c[i] = d[i] - c[i - 1];
+    }
+
+    // DCT-IV to IMDCT
+    //
+    // Using symmetry, expand the DCT-IV to IMDCT. Multiply by the scale factor as this
+    // is done.
+    for (sa, &sc) in a.iter_mut().zip(c.iter()) {
+        // Region a is a scaled copy of region c.
+        *sa = scale * sc;
+    }
+
+    for ((sd, sc), &sb) in d.iter_mut().zip(c.iter_mut().rev()).zip(b.iter()) {
+        // Region d is a scaled copy of region b.
+        // Region c is a reversed and scaled copy of region b.
+        let s = scale * sb;
+        *sd = s;
+        *sc = s;
+    }
+
+    for (sb, &sa) in b.iter_mut().zip(a.iter().rev()) {
+        // Region b is an inverted copy of region c. Region c was overwrittern above,
+        // but region a is a copy of the original region c.
+        *sb = -1.0 * sa;
+    }
+}
+
+/// Computes a N-point DCT-II in-place using an N/2-point complex FFT, rather than `Dct`'s direct
+/// O(N^2) evaluation.
+///
+/// This packs the even/odd-reordered input into a single N/2-point complex FFT using the
+/// standard trick for computing the FFT of two real sequences (or, equivalently here, one real
+/// sequence of twice the length) from a single complex FFT, then derives the DCT-II from the
+/// unpacked spectrum as per \[3\].
+///
+/// `unpack_tw` and `rot_tw` are the precomputed twiddle tables for the unpacking and final
+/// rotation steps respectively (see their fields on `Backend::Fft`), and `v`, `c`, `vv` are
+/// reusable scratch buffers sized to match `buf`; none of the five are allocated here, so this
+/// function performs no heap allocation.
+#[allow(clippy::too_many_arguments)]
+fn dct_ii_via_fft(
+    fft: &Fft,
+    unpack_tw: &[Complex32],
+    rot_tw: &[Complex32],
+    v: &mut [f32],
+    c: &mut [Complex32],
+    vv: &mut [Complex32],
+    buf: &mut [f32],
+) {
+    let n = buf.len();
+    let nh = n / 2;
+
+    // Reorder the input: even-indexed samples to the front, odd-indexed samples reversed to
+    // the back. This is the same reordering `Dct` performs internally to reduce the DCT-II to
+    // a same-length FFT; here it additionally lets that FFT be computed at half-length.
+    for i in 0..nh {
+        v[i] = buf[2 * i];
+        v[n - 1 - i] = buf[2 * i + 1];
+    }
+
+    // Pack the N-point real sequence `v` into an N/2-point complex sequence and transform it.
+    for (c, v) in c.iter_mut().zip(v.chunks_exact(2)) {
+        *c = Complex32::new(v[0], v[1]);
+    }
+
+    fft.fft(c);
+
+    fn conj(c: Complex32) -> Complex32 {
+        Complex32::new(c.re, -c.im)
+    }
+
+    // Unpack the N/2-point complex FFT into the N-point real FFT of `v`, `vv`, for k=0..=N/2.
+    // The rest of `vv` (k=N/2+1..N-1) is not computed here since the conjugate symmetry of the
+    // FFT of a real sequence, `vv[N-k] = conj(vv[k])`, is applied directly below instead.
+    for (k, vv) in vv.iter_mut().enumerate() {
+        let ck = c[k % nh];
+        let cnk = conj(c[(nh - k) % nh]);
+
+        let even = Complex32::new((ck.re + cnk.re) * 0.5, (ck.im + cnk.im) * 0.5);
+        let odd = Complex32::new((ck.re - cnk.re) * 0.5, (ck.im - cnk.im) * 0.5);
+
+        // V[k] = even + (-i * tw) * odd, i.e. the standard real-FFT-from-half-length-complex-
+        // FFT unpacking formula.
+        *vv = even + odd * unpack_tw[k];
+    }
+
+    // Derive the DCT-II from the real FFT of `v` via a final rotation by `exp(-i*pi*k/(2N))`.
+    for (k, b) in buf.iter_mut().enumerate() {
+        let vk = if k <= nh { vv[k] } else { conj(vv[n - k]) };
+
+        *b = (vk * rot_tw[k]).re;
+    }
+}
+
+/// Modified Discrete Cosine Transform (MDCT).
+///
+/// Computes the forward MDCT directly from its definition: each of the N output coefficients is
+/// a sum over all 2N input samples against the MDCT basis.
+///
+/// Unlike [`Imdct`], this is not reduced to a DCT-II. The recurrence `Imdct` uses to expand a
+/// DCT-IV to an IMDCT only inverts cleanly because it is applied to the DCT-II's *output*;
+/// running it in reverse to recover the DCT-II's *input* implicitly requires the DCT-II to be
+/// self-adjoint, which it is not, so that approach produces incorrect coefficients (see the
+/// history of this module). The direct `O(N^2)` evaluation below is therefore a
+/// correctness-forced fallback, not a performance choice: it is used because no fast MDCT-to-
+/// DCT-IV factorization has been implemented here, not because one doesn't exist. At the
+/// 8192-point maximum this module allows, a single call is approximately 134 million
+/// multiply-adds, markedly worse than `Imdct`'s `O(N^2)` `Dct` backend (which at least avoids a
+/// second N-length pass) or its `O(N log N)` `Fft` backend. Callers computing forward transforms
+/// of large blocks on a hot path should keep N modest or budget for this cost accordingly; a
+/// real factorization (e.g. via \[1\]) would be needed to lift this ceiling.
+///
+/// \[1\] Mu-Huo Cheng and Yu-Hsin Hsu, "Fast IMDCT and MDCT algorithms - a matrix approach,"
+///       in IEEE Transactions on Signal Processing, vol. 51, no. 1, pp. 221-229, Jan. 2003,
+///       doi: 10.1109/TSP.2002.806566.
+pub struct Mdct {
+    n: u32,
+}
+
+impl Mdct {
+    /// Instantiate a N-point MDCT.
+    ///
+    /// The value of `n` must be divisible by 4, and less-than or equal to 8192. Unlike `Imdct`'s
+    /// `Fft` backend, the direct summation `Mdct::mdct` uses places no power-of-two (or similar)
+    /// constraint on N; this module requires N divisible by 4 purely for consistency with
+    /// `Imdct`'s constraint, so the two transforms accept the same sizes.
+    pub fn new(n: u32) -> Mdct {
+        assert_eq!(n % 4, 0, "n must be divisible by 4");
+        // This limitation is somewhat arbitrary, but a limit must be set somewhere.
+        assert!(n <= 8192, "maximum of 8192-point mdct");
+
+        Mdct { n }
+    }
+
+    /// Performs the N-point Modified Discrete Cosine Transform.
+    ///
+    /// The number of input samples in `src`, 2N, must equal twice the value `Mdct` was
+    /// instantiated with. The length of the output slice, `dst`, must equal N. Failing to meet
+    /// these requirements will throw an assertion.
+    ///
+    /// This function performs no windowing of the input. Each output sample will be multiplied
+    /// by `scale`. Typically, scale will equal `sqrt(1.0 / N)`, though each application will
+    /// vary.
+    pub fn mdct(&mut self, src: &[f32], dst: &mut [f32], scale: f32) {
+        let n2 = self.n as usize;
+
+        assert_eq!(src.len(), n2 << 1);
+        assert_eq!(dst.len(), n2);
+
+        // Unlike `Imdct`, the pre/post-rotation trick `Imdct` uses to reduce the transform to a
+        // DCT-II does not carry over cleanly to the forward direction, since the DCT-II this
+        // module has access to is not self-adjoint. Compute the MDCT directly from its
+        // definition instead.
+        let pi_4n = f64::consts::PI / f64::from(4 * n2 as u32);
+
+        for (k, dst) in dst.iter_mut().enumerate() {
+            let mut accum = 0.0;
+
+            for (n, &src) in src.iter().enumerate() {
+                let angle = pi_4n * f64::from((2 * n + 1 + n2) as u32 * (2 * k as u32 + 1));
+                accum += f64::from(src) * angle.cos();
+            }
+
+            *dst = (f64::from(scale) * accum) as f32;
+        }
+    }
+}
+
+/// Generates a sine synthesis window of length `n` as used by, e.g., MP3 and Vorbis.
+///
+/// `w[i] = sin(pi / n * (i + 0.5))`
+pub fn sine_window(n: u32) -> Vec<f32> {
+    let c = f64::consts::PI / f64::from(n);
+    (0..n).map(|i| (c * (f64::from(i) + 0.5)).sin() as f32).collect()
+}
+
+/// Generates a Kaiser-Bessel-derived (KBD) synthesis window of length `n` as used by, e.g.,
+/// AC-3 and AAC.
+///
+/// `alpha` controls the trade-off between main-lobe width and side-lobe suppression. AC-3 and
+/// AAC both use `alpha = 5.0`.
+pub fn kbd_window(n: u32, alpha: f64) -> Vec<f32> {
+    let n = n as usize;
+    let half = n / 2;
+
+    // Zeroth-order modified Bessel function of the first kind.
+    fn bessel_i0(x: f64) -> f64 {
+        let mut sum = 1.0;
+        let mut term = 1.0;
+
+        for k in 1..32 {
+            term *= (x / 2.0) / f64::from(k);
+            sum += term * term;
+        }
+
+        sum
+    }
+
+    let alpha_pi = alpha * f64::consts::PI;
+
+    // Build the Kaiser window over the first half, then accumulate it to derive the KBD window
+    // as per the standard "Kaiser-Bessel derived" construction.
+    let mut kaiser = vec![0f64; half + 1];
+
+    for (i, k) in kaiser.iter_mut().enumerate() {
+        let t = (2.0 * i as f64) / half as f64 - 1.0;
+        *k = bessel_i0(alpha_pi * (1.0 - t * t).max(0.0).sqrt()) / bessel_i0(alpha_pi);
+    }
+
+    let sum: f64 = kaiser.iter().sum();
+
+    let mut window = vec![0f32; n];
+    let mut acc = 0.0;
+
+    for i in 0..half {
+        acc += kaiser[i];
+        let w = (acc / sum).sqrt();
+        window[i] = w as f32;
+        window[n - 1 - i] = w as f32;
+    }
+
+    window
+}
+
+/// Inverse Modified Discrete Sine Transform (IMDST).
+///
+/// The sine-domain companion to [`Imdct`], needed by transform codecs and alias-reduction
+/// schemes that operate on the complex lapped transform (MDCT as the real part, MDST as the
+/// imaginary part). Rather than duplicating `Imdct`'s region-remapping recurrence with a sine
+/// pre-rotation table, this reduces the IMDST to the IMDCT via the standard DST-IV/DCT-IV
+/// reversal identity, `DST-IV(x)[k] = DCT-IV(x')[N-1-k]`, where `x'[n] = (-1)^n * x[n]`, which
+/// for the IMDCT/IMDST pair becomes `imdst(X)[n] = imdct(X')[2N-1-n]`, where
+/// `X'[k] = -(-1)^k * X[k]`. This lets `Imdst` reuse `Imdct` directly instead of maintaining a
+/// second, parallel fast-transform implementation. `Imdst` always uses `Imdct`'s default
+/// ([`ImdctBackend::Dct`]) backend; there is no constructor taking another backend.
+pub struct Imdst {
+    imdct: Imdct,
+    n: u32,
+    folded: Vec<f32>,
+}
+
+impl Imdst {
+    /// Instantiate a N-point IMDST.
+    ///
+    /// The value of `n` must be divisible by 4, and less-than or equal to 8192.
+    pub fn new(n: u32) -> Imdst {
+        Imdst { imdct: Imdct::new(n), n, folded: vec![0f32; n as usize] }
+    }
+
+    /// Performs the N-point Inverse Modified Discrete Sine Transform.
+    ///
+    /// The number of input samples in `src`, N, must equal the value `Imdst` was instantiated
+    /// with. The length of the output slice, `dst`, must equal 2N. As with `Imdct::imdct`, each
+    /// sample is multiplied by `scale`.
+    pub fn imdst(&mut self, src: &[f32], dst: &mut [f32], scale: f32) {
+        let n2 = self.n as usize;
+
+        assert_eq!(dst.len(), n2 << 1);
+        assert_eq!(src.len(), n2);
+
+        for (i, (f, &s)) in self.folded.iter_mut().zip(src).enumerate() {
+            *f = if i & 1 == 0 { -s } else { s };
+        }
+
+        self.imdct.imdct(&self.folded, dst, scale);
+        dst.reverse();
+    }
+}
+
+/// A combined IMDCT/IMDST transform.
+///
+/// The MDCT and MDST together form a complex lapped transform (the MDCT the real part, the
+/// MDST the imaginary part) that is useful for spectral analysis and aliasing cancellation.
+/// `ImdctImdst` computes both from a single `Imdct` instance, which always uses the default
+/// ([`ImdctBackend::Dct`]) backend; there is no constructor taking another backend.
+///
+/// This shares what can actually be shared between the two transforms: one backend (so one set
+/// of tables, rather than the two full copies `Imdct` + `Imdst` would otherwise each
+/// allocate), and the pre-rotation
+/// multiply, which [`Imdst`]'s sign-flip fold turns out to commute with (flipping the sign of
+/// `src[i]` before multiplying by `table[i]` is the same as multiplying first and then flipping
+/// the sign of the product), so it only needs to be computed once. What is *not* shared is the
+/// DCT-II itself, which dominates the cost of either transform: the sign-flipped fold changes
+/// every other sample feeding into it, and because this module's DCT-II is not self-adjoint,
+/// there is no cheap way to derive one DCT-II's output from the other's (confirmed numerically:
+/// the two outputs are not related by any fixed permutation, reversal, or sign pattern). So this
+/// still runs two independent `O(N^2)` DCT-II passes; only the `O(N)` surrounding
+/// work is shared.
+pub struct ImdctImdst {
+    imdct: Imdct,
+    n: u32,
+}
+
+impl ImdctImdst {
+    /// Instantiate a combined N-point IMDCT/IMDST.
+    ///
+    /// The value of `n` must be divisible by 4, and less-than or equal to 8192.
+    pub fn new(n: u32) -> ImdctImdst {
+        ImdctImdst { imdct: Imdct::new(n), n }
+    }
+
+    /// Performs the N-point IMDCT and IMDST, writing the former into `dst_re` and the latter
+    /// into `dst_im`. Both `dst_re` and `dst_im` must be 2N in length, and `src` must be N in
+    /// length. As with `Imdct::imdct`, each output sample is multiplied by `scale`.
+    pub fn imdct_imdst(&mut self, src: &[f32], dst_re: &mut [f32], dst_im: &mut [f32], scale: f32) {
+        let n2 = self.n as usize;
+
+        assert_eq!(dst_re.len(), n2 << 1);
+        assert_eq!(dst_im.len(), n2 << 1);
+        assert_eq!(src.len(), n2);
+
+        // Pre-rotate once into dst_re, then derive the IMDST's sign-flipped DCT-II input from
+        // the result instead of repeating the multiply against `src` (see the struct doc for why
+        // this is valid).
+        self.imdct.prerotate(src, dst_re);
+
+        for i in 0..n2 {
+            let v = dst_re[n2 + i];
+            dst_im[n2 + i] = if i & 1 == 0 { -v } else { v };
+        }
+
+        self.imdct.dct_ii_and_remap(dst_re, scale);
+        self.imdct.dct_ii_and_remap(dst_im, scale);
+
+        // Matches Imdst::imdst's final reversal, which derives the IMDST from the IMDCT of the
+        // folded input via the DST-IV/DCT-IV reversal identity (see [`Imdst`]).
+        dst_im.reverse();
+    }
+}
+
+/// A lapped MDCT synthesis filterbank.
+///
+/// `Imdct` on its own "performs no windowing" and leaves overlap-add to the caller, which means
+/// every codec using it has to re-implement the same stateful boilerplate. `LappedImdct` wraps
+/// an `Imdct` with a synthesis window and a half-block carry buffer to perform the standard
+/// overlapped MDCT synthesis used by codecs such as MP3, Vorbis, and AAC: each call transforms N
+/// frequency coefficients to 2N windowed time-domain samples, adds the first half to the saved
+/// tail of the previous block, and carries the second half forward for the next call.
+pub struct LappedImdct {
+    imdct: Imdct,
+    n: u32,
+    window: Vec<f32>,
+    overlap: Vec<f32>,
+}
+
+impl LappedImdct {
+    /// Instantiate a N-point lapped IMDCT using the provided synthesis window.
+    ///
+    /// The `window` must contain `2 * n` coefficients. See [`sine_window`] and [`kbd_window`]
+    /// for two common windows, or supply a custom window.
+    pub fn new(n: u32, window: &[f32]) -> LappedImdct {
+        assert_eq!(window.len(), (2 * n) as usize, "window must contain 2n coefficients");
+
+        LappedImdct { imdct: Imdct::new(n), n, window: window.to_vec(), overlap: vec![0f32; n as usize] }
+    }
+
+    /// Resets the overlap-add state, e.g., after a seek.
+    pub fn reset(&mut self) {
+        for s in self.overlap.iter_mut() {
+            *s = 0.0;
+        }
+    }
+
+    /// Reconfigures the filterbank for a new block size and/or window, as is required when a
+    /// codec transitions between long and short blocks.
+    ///
+    /// The overlap carried over from the previous block is resized to match the new block's
+    /// left-half length by truncating or zero-padding its tail, keeping its leading samples in
+    /// place. This is exact reconstruction only when the previous block's overlap region and the
+    /// new block's left half-window agree sample-for-sample over their shared length (e.g. equal
+    /// block sizes, or a plain window change at a fixed size). **It is not a general solution for
+    /// genuine asymmetric long/short transitions**: codecs such as MP3 and Vorbis handle those
+    /// with dedicated transition windows (e.g. start/stop blocks) specifically shaped so the
+    /// overlap region lines up despite the size change, and callers transitioning block sizes
+    /// must supply such a window rather than relying on this method to reshape an
+    /// incompatible overlap on their behalf; truncating/padding an overlap that was computed
+    /// against a differently-shaped window is aliasing, not a fallback.
+    pub fn set_block_size(&mut self, n: u32, window: &[f32]) {
+        assert_eq!(window.len(), (2 * n) as usize, "window must contain 2n coefficients");
+
+        let mut overlap = vec![0f32; n as usize];
+        let copy_len = overlap.len().min(self.overlap.len());
+        overlap[..copy_len].copy_from_slice(&self.overlap[..copy_len]);
+
+        self.imdct = Imdct::new(n);
+        self.n = n;
+        self.window = window.to_vec();
+        self.overlap = overlap;
+    }
+
+    /// Performs the N-point lapped Inverse Modified Discrete Cosine Transform.
+    ///
+    /// The number of input coefficients in `src` must equal N. The length of the output slice,
+    /// `dst`, must equal N. As with `Imdct::imdct`, each sample is multiplied by `scale`.
+    pub fn lapped_imdct(&mut self, src: &[f32], dst: &mut [f32], scale: f32) {
+        let n = self.n as usize;
+
+        assert_eq!(src.len(), n);
+        assert_eq!(dst.len(), n);
+
+        let mut block = vec![0f32; 2 * n];
+        self.imdct.imdct(src, &mut block, scale);
+
+        for (s, w) in block.iter_mut().zip(&self.window) {
+            *s *= w;
+        }
+
+        let (first, second) = block.split_at(n);
+
+        for (d, (&cur, &tail)) in dst.iter_mut().zip(first.iter().zip(&self.overlap)) {
+            *d = cur + tail;
+        }
+
+        self.overlap.copy_from_slice(second);
+    }
+}
+
+/// Fixed-point fractional bits used throughout `ImdctFixed`.
+const FIXED_FRAC_BITS: u32 = 15;
+
+/// Converts a `f64` to a Q15 fixed-point value, i.e. `round(x * 2^15)`.
+fn to_q15(x: f64) -> i32 {
+    (x * f64::from(1i32 << FIXED_FRAC_BITS)).round() as i32
+}
+
+/// Multiplies two Q15 values, returning a Q15 result, rounding to nearest.
+fn q15_mul(a: i32, b: i32) -> i32 {
+    let product = i64::from(a) * i64::from(b);
+    ((product + (1 << (FIXED_FRAC_BITS - 1))) >> FIXED_FRAC_BITS) as i32
+}
+
+/// Right-shifts `x` by `shift` bits, rounding to nearest. A negative `shift` left-shifts instead.
+fn round_shift(x: i32, shift: i32) -> i32 {
+    match shift {
+        0 => x,
+        s if s > 0 => ((i64::from(x) + (1i64 << (s - 1))) >> s) as i32,
+        s => (i64::from(x) << -s) as i32,
+    }
+}
+
+/// Fixed-point (`i32`) Inverse Modified Discrete Cosine Transform.
+///
+/// `Imdct` is implemented entirely in `f32`, which precludes bit-exact, reproducible decoding on
+/// integer-only targets (e.g., many embedded and WASM platforms lacking a hardware FPU).
+/// `ImdctFixed` performs the same DCT-II -> DCT-IV -> IMDCT region-mapping algorithm in Q15
+/// fixed-point arithmetic with 64-bit accumulators and explicit rounding shifts.
+///
+/// Because there is no fixed-point equivalent of `Dct` available, the DCT-II step is computed
+/// directly from its definition rather than through a fast algorithm. This is `O(N^2)` in both
+/// time and the size of the precomputed basis table, so `n` is limited to 1152 rather than the
+/// 8192 `Imdct` allows.
+pub struct ImdctFixed {
+    n: u32,
+    /// Q15 pre-rotation table, `2*cos(...)`, as used by `Imdct`.
+    table: Vec<i32>,
+    /// Q15 DCT-II basis, `cos(pi/N*(i+0.5)*k)`, stored row-major as `[k][i]`.
+    basis: Vec<i32>,
+}
+
+impl ImdctFixed {
+    /// Instantiate a N-point fixed-point IMDCT.
+    ///
+    /// The value of `n` must be divisible by 4, and less-than or equal to 1152.
+    pub fn new(n: u32) -> ImdctFixed {
+        assert_eq!(n % 4, 0, "n must be divisible by 4");
+        assert!(n <= 1152, "maximum of 1152-point fixed-point imdct");
+
+        let c = f64::consts::PI / f64::from(2 * 2 * n);
+
+        let table: Vec<i32> =
+            (0..n).map(|i| to_q15(2.0 * (c * f64::from(2 * i + 1)).cos())).collect();
+
+        let n_usize = n as usize;
+        let mut basis = vec![0i32; n_usize * n_usize];
+
+        for k in 0..n_usize {
+            for i in 0..n_usize {
+                let theta = f64::consts::PI / f64::from(n) * (i as f64 + 0.5) * k as f64;
+                basis[k * n_usize + i] = to_q15(theta.cos());
+            }
+        }
+
+        ImdctFixed { n, table, basis }
+    }
+
+    /// Performs the N-point fixed-point Inverse Modified Discrete Cosine Transform.
+    ///
+    /// `src` must contain N Q15 samples, and `dst` must be 2N samples long. `scale_q15` is a
+    /// Q15 fixed-point scale factor applied to every output sample, analogous to `Imdct::imdct`'s
+    /// `scale` parameter. `output_shift` rescales the Q15 result into the caller's desired
+    /// output Q-format: a positive shift divides (rounding to nearest), a negative shift
+    /// multiplies.
+    pub fn imdct(&self, src: &[i32], dst: &mut [i32], scale_q15: i32, output_shift: i32) {
         let n2 = self.n as usize;
         let n = n2 << 1;
         let n4 = n2 >> 1;
 
-        assert_eq!(dst.len(), n);
         assert_eq!(src.len(), n2);
+        assert_eq!(dst.len(), n);
 
-        // Pre-process the input and place it in the second-half of dst.
-        for ((ds, &src), &cos) in dst[n2..].iter_mut().zip(src).zip(&self.table) {
-            *ds = src * cos;
+        // Pre-rotate the input by the Q15 cosine table.
+        let mut folded = vec![0i32; n2];
+
+        for ((f, &s), &t) in folded.iter_mut().zip(src).zip(&self.table) {
+            *f = q15_mul(s, t);
         }
 
-        // Compute the DCT-II in-place using the pre-processed samples that reside in the second-
-        // half of dst.
-        self.dct.dct_ii_inplace(&mut dst[n2..]);
+        // Direct DCT-II, accumulating in 64-bit to avoid overflow and rounding only once per
+        // output sample.
+        let mut transformed = vec![0i32; n2];
 
-        // DCT-II to DCT-IV
-        //
-        // Split dst into 4 evenly sized N/4 regions: [ a, b, c, d ]. Regions c & d contain the
-        // DCT-II transformed samples from the previous step. After this step, regions b & c will
-        // contain the DCT-II transformed samples.
-        let (a, b) = dst.split_at_mut(n4);
-        let (b, c) = b.split_at_mut(n4);
-        let (c, d) = c.split_at_mut(n4);
+        for (k, t) in transformed.iter_mut().enumerate() {
+            let row = &self.basis[k * n2..(k + 1) * n2];
+
+            let mut acc: i64 = 0;
+
+            for (&b, &f) in row.iter().zip(&folded) {
+                acc += i64::from(b) * i64::from(f);
+            }
+
+            *t = ((acc + (1 << (FIXED_FRAC_BITS - 1))) >> FIXED_FRAC_BITS) as i32;
+        }
 
-        // Map c to b.
-        b[0] = -0.5 * c[0];
+        // DCT-II to DCT-IV, identical recurrence to `Imdct::imdct`, but in Q15 integer
+        // arithmetic (addition and subtraction do not change the Q-format).
+        let (tc, td) = transformed.split_at(n4);
+
+        let mut rb = vec![0i32; n4];
+        let mut rc = vec![0i32; n4];
+
+        rb[0] = round_shift(-tc[0], 1);
 
         for i in 1..n4 {
-            b[i] = -1.0 * (c[i] + b[i - 1]);
+            rb[i] = -(tc[i] + rb[i - 1]);
         }
 
-        // Map d to c.
-        c[0] = d[0] + b[n4 - 1];
+        rc[0] = td[0] + rb[n4 - 1];
 
         for i in 1..n4 {
-            c[i] = d[i] - c[i - 1];
+            rc[i] = td[i] - rc[i - 1];
         }
 
-        // DCT-IV to IMDCT
-        //
-        // Using symmetry, expand the DCT-IV to IMDCT. Multiply by the scale factor as this
-        // is done.
-        for (sa, &sc) in a.iter_mut().zip(c.iter()) {
-            // Region a is a scaled copy of region c.
-            *sa = scale * sc;
+        // DCT-IV to IMDCT, expanding via symmetry and applying the scale factor & caller's
+        // output Q-format.
+        let (a, b) = dst.split_at_mut(n4);
+        let (b, c) = b.split_at_mut(n4);
+        let (c, d) = c.split_at_mut(n4);
+
+        for (sa, &sc) in a.iter_mut().zip(&rc) {
+            *sa = round_shift(q15_mul(scale_q15, sc), output_shift);
         }
 
-        for ((sd, sc), &sb) in d.iter_mut().zip(c.iter_mut().rev()).zip(b.iter()) {
-            // Region d is a scaled copy of region b.
-            // Region c is a reversed and scaled copy of region b.
-            let s = scale * sb;
+        for ((sd, sc), &rb) in d.iter_mut().zip(c.iter_mut().rev()).zip(&rb) {
+            let s = round_shift(q15_mul(scale_q15, rb), output_shift);
             *sd = s;
             *sc = s;
         }
 
         for (sb, &sa) in b.iter_mut().zip(a.iter().rev()) {
-            // Region b is an inverted copy of region c. Region c was overwrittern above,
-            // but region a is a copy of the original region c.
-            *sb = -1.0 * sa;
+            *sb = -sa;
         }
     }
 }
@@ -157,6 +823,52 @@ mod tests {
         }
     }
 
+    fn mdct_analytical(x: &[f32], y: &mut [f32], scale: f64) {
+        assert!(x.len() == 2 * y.len());
+
+        // Generates N outputs from 2N inputs.
+        let n_in = x.len();
+        let n_out = x.len() >> 1;
+
+        let pi_2n = f64::consts::PI / (2 * n_in) as f64;
+
+        for k in 0..n_out {
+            let mut accum = 0.0;
+
+            for n in 0..n_in {
+                accum +=
+                    f64::from(x[n]) * (pi_2n * ((2 * n + 1 + n_out) * (2 * k + 1)) as f64).cos();
+            }
+
+            y[k] = (scale * accum) as f32;
+        }
+    }
+
+    #[test]
+    fn verify_mdct() {
+        const TEST_VECTOR: [f32; 64] = [
+            1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0, 11.0, 12.0, 13.0, 14.0, 15.0, 16.0,
+            17.0, 18.0, 19.0, 20.0, 21.0, 22.0, 23.0, 24.0, 25.0, 26.0, 27.0, 28.0, 29.0, 30.0,
+            31.0, 32.0, 33.0, 34.0, 35.0, 36.0, 37.0, 38.0, 39.0, 40.0, 41.0, 42.0, 43.0, 44.0,
+            45.0, 46.0, 47.0, 48.0, 49.0, 50.0, 51.0, 52.0, 53.0, 54.0, 55.0, 56.0, 57.0, 58.0,
+            59.0, 60.0, 61.0, 62.0, 63.0, 64.0,
+        ];
+
+        let mut actual = [0f32; 32];
+        let mut expected = [0f32; 32];
+
+        let scale = (2.0f64 / 64.0).sqrt();
+
+        mdct_analytical(&TEST_VECTOR, &mut expected, scale);
+
+        let mut mdct = Mdct::new(32);
+        mdct.mdct(&TEST_VECTOR, &mut actual, scale as f32);
+
+        for i in 0..32 {
+            assert!((actual[i] - expected[i]).abs() < 0.00001);
+        }
+    }
+
     #[test]
     fn verify_imdct() {
         const TEST_VECTOR: [f32; 32] = [
@@ -179,4 +891,180 @@ mod tests {
             assert!((actual[i] - expected[i]).abs() < 0.00001);
         }
     }
+
+    #[test]
+    fn verify_imdct_fft_backend() {
+        const TEST_VECTOR: [f32; 32] = [
+            1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0, 11.0, 12.0, 13.0, 14.0, 15.0, 16.0,
+            17.0, 18.0, 19.0, 20.0, 21.0, 22.0, 23.0, 24.0, 25.0, 26.0, 27.0, 28.0, 29.0, 30.0,
+            31.0, 32.0,
+        ];
+
+        let mut actual = [0f32; 64];
+        let mut expected = [0f32; 64];
+
+        let scale = (2.0f64 / 64.0).sqrt();
+
+        imdct_analytical(&TEST_VECTOR, &mut expected, scale);
+
+        let mut mdct = Imdct::with_backend(32, ImdctBackend::Fft);
+        mdct.imdct(&TEST_VECTOR, &mut actual, scale as f32);
+
+        for i in 0..64 {
+            assert!((actual[i] - expected[i]).abs() < 0.0001);
+        }
+    }
+
+    #[test]
+    fn verify_imdct_n12() {
+        const TEST_VECTOR: [f32; 12] = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0, 11.0, 12.0];
+
+        let mut actual = [0f32; 24];
+        let mut expected = [0f32; 24];
+
+        let scale = (2.0f64 / 24.0).sqrt();
+
+        imdct_analytical(&TEST_VECTOR, &mut expected, scale);
+
+        let mut mdct = Imdct::new(12);
+        mdct.imdct(&TEST_VECTOR, &mut actual, scale as f32);
+
+        for i in 0..24 {
+            assert!((actual[i] - expected[i]).abs() < 0.00001);
+        }
+    }
+
+    #[test]
+    fn verify_imdct_n36() {
+        let test_vector: Vec<f32> = (1..=36).map(|i| i as f32).collect();
+
+        let mut actual = [0f32; 72];
+        let mut expected = [0f32; 72];
+
+        let scale = (2.0f64 / 72.0).sqrt();
+
+        imdct_analytical(&test_vector, &mut expected, scale);
+
+        let mut mdct = Imdct::new(36);
+        mdct.imdct(&test_vector, &mut actual, scale as f32);
+
+        for i in 0..72 {
+            assert!((actual[i] - expected[i]).abs() < 0.00001);
+        }
+    }
+
+    fn imdst_analytical(x: &[f32], y: &mut [f32], scale: f64) {
+        assert!(y.len() == 2 * x.len());
+
+        let n_in = x.len();
+        let n_out = x.len() << 1;
+
+        let pi_2n = f64::consts::PI / (2 * n_out) as f64;
+
+        for i in 0..n_out {
+            let mut accum = 0.0;
+
+            for j in 0..n_in {
+                accum +=
+                    f64::from(x[j]) * (pi_2n * ((2 * i + 1 + n_in) * (2 * j + 1)) as f64).sin();
+            }
+
+            y[i] = (scale * accum) as f32;
+        }
+    }
+
+    #[test]
+    fn verify_imdst() {
+        const TEST_VECTOR: [f32; 32] = [
+            1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0, 11.0, 12.0, 13.0, 14.0, 15.0, 16.0,
+            17.0, 18.0, 19.0, 20.0, 21.0, 22.0, 23.0, 24.0, 25.0, 26.0, 27.0, 28.0, 29.0, 30.0,
+            31.0, 32.0,
+        ];
+
+        let mut actual = [0f32; 64];
+        let mut expected = [0f32; 64];
+
+        let scale = (2.0f64 / 64.0).sqrt();
+
+        imdst_analytical(&TEST_VECTOR, &mut expected, scale);
+
+        let mut imdst = Imdst::new(32);
+        imdst.imdst(&TEST_VECTOR, &mut actual, scale as f32);
+
+        for i in 0..64 {
+            assert!((actual[i] - expected[i]).abs() < 0.00001);
+        }
+    }
+
+    #[test]
+    fn verify_imdct_imdst() {
+        const TEST_VECTOR: [f32; 32] = [
+            1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0, 11.0, 12.0, 13.0, 14.0, 15.0, 16.0,
+            17.0, 18.0, 19.0, 20.0, 21.0, 22.0, 23.0, 24.0, 25.0, 26.0, 27.0, 28.0, 29.0, 30.0,
+            31.0, 32.0,
+        ];
+
+        let mut expected_re = [0f32; 64];
+        let mut expected_im = [0f32; 64];
+
+        let scale = (2.0f64 / 64.0).sqrt();
+
+        imdct_analytical(&TEST_VECTOR, &mut expected_re, scale);
+        imdst_analytical(&TEST_VECTOR, &mut expected_im, scale);
+
+        let mut actual_re = [0f32; 64];
+        let mut actual_im = [0f32; 64];
+
+        let mut imdct_imdst = ImdctImdst::new(32);
+        imdct_imdst.imdct_imdst(&TEST_VECTOR, &mut actual_re, &mut actual_im, scale as f32);
+
+        for i in 0..64 {
+            assert!((actual_re[i] - expected_re[i]).abs() < 0.00001);
+            assert!((actual_im[i] - expected_im[i]).abs() < 0.00001);
+        }
+    }
+
+    #[test]
+    fn verify_imdct_fixed_matches_float() {
+        const TEST_VECTOR: [f32; 32] = [
+            1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0, 11.0, 12.0, 13.0, 14.0, 15.0, 16.0,
+            17.0, 18.0, 19.0, 20.0, 21.0, 22.0, 23.0, 24.0, 25.0, 26.0, 27.0, 28.0, 29.0, 30.0,
+            31.0, 32.0,
+        ];
+
+        let scale = (2.0f64 / 64.0).sqrt();
+
+        let mut expected = [0f32; 64];
+        let mut imdct = Imdct::new(32);
+        imdct.imdct(&TEST_VECTOR, &mut expected, scale as f32);
+
+        // The test vector and scale are small enough to fit comfortably within Q15 without
+        // needing to pre-scale the input.
+        let src_fixed: Vec<i32> = TEST_VECTOR.iter().map(|&x| to_q15(f64::from(x))).collect();
+        let scale_q15 = to_q15(scale);
+
+        let mut actual_fixed = [0i32; 64];
+        let imdct_fixed = ImdctFixed::new(32);
+        imdct_fixed.imdct(&src_fixed, &mut actual_fixed, scale_q15, 0);
+
+        for (&actual, &expected) in actual_fixed.iter().zip(&expected) {
+            let actual = actual as f64 / f64::from(1i32 << FIXED_FRAC_BITS);
+            assert!((actual - f64::from(expected)).abs() < 0.01);
+        }
+    }
+
+    #[test]
+    fn verify_sine_window_satisfies_princen_bradley() {
+        // The Princen-Bradley condition, w[k]^2 + w[k + N]^2 == 1, is what makes overlap-add
+        // reconstruction exact; a window of the wrong frequency satisfies the assert on length
+        // but silently breaks TDAC.
+        let n = 64;
+        let window = sine_window(n);
+        let half = (n / 2) as usize;
+
+        for k in 0..half {
+            let sum = f64::from(window[k]).powi(2) + f64::from(window[k + half]).powi(2);
+            assert!((sum - 1.0).abs() < 0.0001);
+        }
+    }
 }